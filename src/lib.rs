@@ -0,0 +1,732 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+
+use nom::bytes::complete::{take_while1, take_while_m_n};
+use nom::character::complete::{space0, space1};
+use nom::combinator::{opt, recognize};
+use nom::error::{VerboseError, VerboseErrorKind};
+use nom::multi::separated_list1;
+use nom::sequence::{preceded, tuple};
+use nom::AsChar;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1},
+    error::context,
+    multi::many1,
+    sequence::delimited,
+    IResult,
+};
+
+/// `IResult` specialized to [`VerboseError`] so that failed parses carry the
+/// `context()` label and input position needed to render a [`ParseError`].
+type PResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// A parse failure with enough information to point at the offending
+/// character: the byte offset into the original input where parsing
+/// stalled, the innermost `context()` label active there (e.g. `"atom"`),
+/// and a rendered message with a `^` caret under that column.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub context: Option<&'static str>,
+    rendered: String,
+}
+
+impl ParseError {
+    fn new(input: &str, offset: usize, context: Option<&'static str>) -> Self {
+        let mut rendered = format!("{input}\n");
+        rendered.push_str(&" ".repeat(offset));
+        rendered.push('^');
+        if let Some(ctx) = context {
+            rendered.push_str(&format!(" expected {ctx}"));
+        }
+        Self {
+            offset,
+            context,
+            rendered,
+        }
+    }
+
+    /// Builds a `ParseError` from a failed [`PResult`], computing the
+    /// offset from how much of `input` the deepest failing parser had
+    /// already consumed.
+    fn from_nom(input: &str, err: nom::Err<VerboseError<&str>>) -> Self {
+        let errors = match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.errors,
+            nom::Err::Incomplete(_) => {
+                return Self::new(input, input.len(), None)
+            }
+        };
+        let rest = errors.first().map_or(input, |(i, _)| *i);
+        let offset = input.len() - rest.len();
+        let context = errors.iter().find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(c) => Some(*c),
+            _ => None,
+        });
+        Self::new(input, offset, context)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.rendered)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A single atom, covering both the bracket grammar (`[13CH4+:1]`) and the
+/// bare organic/aromatic subset (`C`, `c`, `Cl`). Optional fields keep the
+/// exact text that was parsed so `Display` round-trips the input: a value
+/// of `None` means the part was absent, not that it defaulted to zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Atom<'a> {
+    /// element symbol, or `*` for a wildcard; lowercase means aromatic
+    pub symbol: &'a str,
+    pub isotope: Option<&'a str>,
+    /// `@` or `@@`
+    pub chirality: Option<&'a str>,
+    pub hcount: Option<usize>,
+    /// exact hydrogen count text as written (`"H"`, `"H1"`, `"H31"`, ...)
+    pub hcount_text: Option<&'a str>,
+    pub charge: i32,
+    /// exact charge text as written (`"+"`, `"++"`, `"+2"`, ...)
+    pub charge_text: Option<&'a str>,
+    pub map_idx: Option<usize>,
+    /// whether this atom was written inside `[...]`
+    pub bracketed: bool,
+}
+
+impl Display for Atom<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.bracketed {
+            return write!(f, "{}", self.symbol);
+        }
+        write!(f, "[")?;
+        if let Some(isotope) = self.isotope {
+            write!(f, "{isotope}")?;
+        }
+        write!(f, "{}", self.symbol)?;
+        if let Some(chirality) = self.chirality {
+            write!(f, "{chirality}")?;
+        }
+        if let Some(text) = self.hcount_text {
+            write!(f, "{text}")?;
+        }
+        if let Some(charge) = self.charge_text {
+            write!(f, "{charge}")?;
+        }
+        if let Some(map_idx) = self.map_idx {
+            write!(f, ":{map_idx}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+fn organic_symbol(s: &str) -> PResult<'_, &str> {
+    alt((
+        tag("Cl"),
+        tag("Br"),
+        tag("B"),
+        tag("C"),
+        tag("N"),
+        tag("O"),
+        tag("S"),
+        tag("P"),
+        tag("F"),
+        tag("I"),
+    ))(s)
+}
+
+fn aromatic_symbol(s: &str) -> PResult<'_, &str> {
+    alt((
+        tag("b"),
+        tag("c"),
+        tag("n"),
+        tag("o"),
+        tag("p"),
+        tag("s"),
+    ))(s)
+}
+
+fn element_symbol(s: &str) -> PResult<'_, &str> {
+    recognize(preceded(
+        take_while_m_n(1, 1, |c: char| c.is_ascii_uppercase()),
+        opt(take_while_m_n(1, 1, |c: char| c.is_ascii_lowercase())),
+    ))(s)
+}
+
+/// Any element symbol a bracket atom may hold, not just the organic subset:
+/// an uppercase letter with an optional lowercase letter (`Si`, `Na`, `H`),
+/// a single-letter aromatic symbol (`b`, `c`, `n`, `o`, `p`, `s`), or `*`.
+fn symbol(s: &str) -> PResult<'_, &str> {
+    context("symbol", alt((element_symbol, aromatic_symbol, tag("*"))))(s)
+}
+
+fn chirality(s: &str) -> PResult<'_, &str> {
+    context("chirality", alt((tag("@@"), tag("@"))))(s)
+}
+
+/// Matches `H` or `H` followed by a digit count, returning the text as
+/// written so callers can tell `H` (implicit 1) from an explicit `H1`.
+fn hcount(s: &str) -> PResult<'_, &str> {
+    context(
+        "hcount",
+        alt((recognize(preceded(char('H'), digit1)), recognize(char('H')))),
+    )(s)
+}
+
+fn hcount_value(text: &str) -> usize {
+    if text.len() == 1 {
+        1
+    } else {
+        text[1..].parse().unwrap()
+    }
+}
+
+fn charge(s: &str) -> PResult<'_, &str> {
+    context(
+        "charge",
+        alt((
+            recognize(preceded(char('+'), digit1)),
+            recognize(preceded(char('-'), digit1)),
+            recognize(many1(char('+'))),
+            recognize(many1(char('-'))),
+        )),
+    )(s)
+}
+
+fn charge_value(text: &str) -> i32 {
+    let sign = if text.starts_with('-') { -1 } else { 1 };
+    let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+    match digits.parse::<i32>() {
+        Ok(n) => sign * n,
+        Err(_) => sign * text.len() as i32,
+    }
+}
+
+fn bracket_atom(s: &str) -> PResult<'_, Expr> {
+    context(
+        "atom",
+        delimited(
+            char('['),
+            tuple((
+                opt(digit1),
+                symbol,
+                opt(chirality),
+                opt(hcount),
+                opt(charge),
+                opt(preceded(char(':'), digit1)),
+            )),
+            char(']'),
+        ),
+    )(s)
+    .map(|(inp, (isotope, sym, chir, h, chg, map_idx))| {
+        let (charge_val, charge_text) = match chg {
+            Some(text) => (charge_value(text), Some(text)),
+            None => (0, None),
+        };
+        (
+            inp,
+            Expr::Atom(Atom {
+                symbol: sym,
+                isotope,
+                chirality: chir,
+                hcount: h.map(hcount_value),
+                hcount_text: h,
+                charge: charge_val,
+                charge_text,
+                map_idx: map_idx.map(|d: &str| d.parse().unwrap()),
+                bracketed: true,
+            }),
+        )
+    })
+}
+
+fn bare_atom(s: &str) -> PResult<'_, Expr> {
+    context("atom", alt((organic_symbol, aromatic_symbol)))(s).map(|(inp, sym)| {
+        (
+            inp,
+            Expr::Atom(Atom {
+                symbol: sym,
+                isotope: None,
+                chirality: None,
+                hcount: None,
+                hcount_text: None,
+                charge: 0,
+                charge_text: None,
+                map_idx: None,
+                bracketed: false,
+            }),
+        )
+    })
+}
+
+fn atom(s: &str) -> PResult<'_, Expr> {
+    context("atom", alt((bracket_atom, bare_atom)))(s)
+}
+
+fn label(s: &str) -> PResult<'_, Expr> {
+    context(
+        "label",
+        alt((
+            recognize(preceded(
+                char('%'),
+                take_while_m_n(2, 2, |c: char| c.is_ascii_digit()),
+            )),
+            recognize(take_while_m_n(1, 1, |c: char| c.is_ascii_digit())),
+        )),
+    )(s)
+    .map(|(inp, d)| (inp, Expr::Label(d)))
+}
+
+fn bond(s: &str) -> PResult<'_, Expr> {
+    context(
+        "bond",
+        alt((
+            tag("."),
+            tag("-"),
+            tag("="),
+            tag("#"),
+            tag("$"),
+            tag(":"),
+            tag("/"),
+            tag("\\"),
+        )),
+    )(s)
+    .map(|(i, o)| (i, Expr::Bond(o)))
+}
+
+// let me just simplify this for now. at each position, I can have an ATOM, a
+// BOND, a LABEL, or a BRANCH, where a BRANCH is itself a delimited sequence of
+// ATOM | BOND | LABEL | BRANCH
+
+#[derive(Debug)]
+pub enum Expr<'a> {
+    Atom(Atom<'a>),
+    Bond(&'a str),
+    Label(&'a str),
+    Branch(Vec<Expr<'a>>),
+}
+
+impl Display for Expr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Atom(atom) => write!(f, "{atom}"),
+            Expr::Bond(s) => write!(f, "{s}"),
+            Expr::Label(l) => write!(f, "{l}"),
+            Expr::Branch(exprs) => {
+                write!(f, "(")?;
+                for expr in exprs {
+                    write!(f, "{expr}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn branch(s: &str) -> PResult<'_, Expr> {
+    context("branch", delimited(char('('), smiles, char(')')))(s)
+        .map(|(i, o)| (i, Expr::Branch(o)))
+}
+
+fn smiles(s: &str) -> PResult<'_, Vec<Expr>> {
+    context("smiles", many1(alt((atom, bond, label, branch))))(s)
+}
+
+#[derive(Debug)]
+pub struct Smiles<'a> {
+    exprs: Vec<Expr<'a>>,
+}
+
+impl<'a> TryFrom<&'a str> for Smiles<'a> {
+    type Error = ParseError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (rest, got) =
+            smiles(value).map_err(|e| ParseError::from_nom(value, e))?;
+        if !rest.is_empty() {
+            let offset = value.len() - rest.len();
+            return Err(ParseError::new(value, offset, Some("smiles")));
+        }
+        Ok(Self { exprs: got })
+    }
+}
+
+impl Display for Smiles<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for g in &self.exprs {
+            write!(f, "{g}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Callbacks for folding over a [`Smiles`] parse tree without matching
+/// [`Expr`] variants directly. All methods default to doing nothing, so
+/// implementors only override the ones they care about.
+pub trait Visitor {
+    fn visit_atom(&mut self, _atom: &Atom) {}
+    fn visit_bond(&mut self, _bond: &str) {}
+    fn visit_ring(&mut self, _label: &str) {}
+    fn enter_branch(&mut self) {}
+    fn exit_branch(&mut self) {}
+}
+
+fn accept_exprs<'a>(exprs: &[Expr<'a>], visitor: &mut impl Visitor) {
+    for e in exprs {
+        match e {
+            Expr::Atom(a) => visitor.visit_atom(a),
+            Expr::Bond(b) => visitor.visit_bond(b),
+            Expr::Label(l) => visitor.visit_ring(l),
+            Expr::Branch(b) => {
+                visitor.enter_branch();
+                accept_exprs(b, visitor);
+                visitor.exit_branch();
+            }
+        }
+    }
+}
+
+impl<'a> Smiles<'a> {
+    /// Drives `visitor` depth-first over this parse tree, calling into
+    /// branches between matching `enter_branch`/`exit_branch` callbacks.
+    pub fn accept(&self, visitor: &mut impl Visitor) {
+        accept_exprs(&self.exprs, visitor);
+    }
+}
+
+fn atom_iter<'x, 'a>(
+    exprs: &'x [Expr<'a>],
+) -> Box<dyn Iterator<Item = &'x Atom<'a>> + 'x> {
+    Box::new(exprs.iter().flat_map(|e| {
+        let boxed: Box<dyn Iterator<Item = &'x Atom<'a>> + 'x> = match e {
+            Expr::Atom(a) => Box::new(std::iter::once(a)),
+            Expr::Bond(_) | Expr::Label(_) => Box::new(std::iter::empty()),
+            Expr::Branch(b) => atom_iter(b),
+        };
+        boxed
+    }))
+}
+
+fn atom_iter_mut<'x, 'a>(
+    exprs: &'x mut [Expr<'a>],
+) -> Box<dyn Iterator<Item = &'x mut Atom<'a>> + 'x> {
+    Box::new(exprs.iter_mut().flat_map(|e| {
+        let boxed: Box<dyn Iterator<Item = &'x mut Atom<'a>> + 'x> = match e {
+            Expr::Atom(a) => Box::new(std::iter::once(a)),
+            Expr::Bond(_) | Expr::Label(_) => Box::new(std::iter::empty()),
+            Expr::Branch(b) => atom_iter_mut(b),
+        };
+        boxed
+    }))
+}
+
+impl<'a> Smiles<'a> {
+    /// Map indices of the atoms that have one; atoms with no `:n` in their
+    /// bracket are omitted.
+    pub fn atoms(&self) -> Box<dyn Iterator<Item = &usize> + '_> {
+        Box::new(atom_iter(&self.exprs).filter_map(|a| a.map_idx.as_ref()))
+    }
+
+    pub fn atoms_mut(&mut self) -> Box<dyn Iterator<Item = &mut usize> + '_> {
+        Box::new(atom_iter_mut(&mut self.exprs).filter_map(|a| a.map_idx.as_mut()))
+    }
+}
+
+/// A bond between two atoms, identified by their indices into
+/// [`Molecule::atoms`]. `order` is the bond symbol that preceded the second
+/// atom or ring closure digit, if any (`None` means a default single or
+/// aromatic bond).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bond<'a> {
+    pub a: usize,
+    pub b: usize,
+    pub order: Option<&'a str>,
+}
+
+/// The molecular graph obtained by lowering a [`Smiles`] parse tree: a flat
+/// list of atoms plus an adjacency list of bonds between them, including
+/// those introduced by ring-closure digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Molecule<'a> {
+    pub atoms: Vec<Atom<'a>>,
+    pub bonds: Vec<Bond<'a>>,
+}
+
+/// Atomic number of the organic-subset elements (`B C N O P S F Cl Br I`)
+/// and their aromatic lowercase forms. Every other symbol, including
+/// bracket-only elements such as `H` or `Na` and the `*` wildcard, maps to
+/// 0 and sorts lowest.
+fn atomic_number(symbol: &str) -> u32 {
+    match symbol {
+        "B" | "b" => 5,
+        "C" | "c" => 6,
+        "N" | "n" => 7,
+        "O" | "o" => 8,
+        "F" => 9,
+        "P" | "p" => 15,
+        "S" | "s" => 16,
+        "Cl" => 17,
+        "Br" => 35,
+        "I" => 53,
+        _ => 0,
+    }
+}
+
+fn count_distinct(values: &[u64]) -> usize {
+    values.iter().copied().collect::<HashSet<_>>().len()
+}
+
+/// Visits `cur` and then its unvisited neighbors in descending `rank`
+/// order, recording each atom index in `order` as it is reached.
+fn visit(
+    cur: usize,
+    adjacency: &[Vec<usize>],
+    rank: &impl Fn(usize) -> (u64, u32, i32),
+    visited: &mut [bool],
+    order: &mut Vec<usize>,
+) {
+    visited[cur] = true;
+    order.push(cur);
+    let mut neighbors: Vec<usize> = adjacency[cur]
+        .iter()
+        .copied()
+        .filter(|&j| !visited[j])
+        .collect();
+    neighbors.sort_by_key(|&j| std::cmp::Reverse(rank(j)));
+    for j in neighbors {
+        if !visited[j] {
+            visit(j, adjacency, rank, visited, order);
+        }
+    }
+}
+
+impl<'a> Molecule<'a> {
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.atoms.len()];
+        for bond in &self.bonds {
+            adjacency[bond.a].push(bond.b);
+            adjacency[bond.b].push(bond.a);
+        }
+        adjacency
+    }
+
+    /// Morgan extended-connectivity invariants: start each atom's
+    /// invariant at its degree, then repeatedly replace it with the sum of
+    /// its neighbors' invariants as long as doing so increases the number
+    /// of distinct values. Stops and returns the invariants from the round
+    /// before the distinct count stopped growing.
+    fn morgan_invariants(&self, adjacency: &[Vec<usize>]) -> Vec<u64> {
+        let mut invariants: Vec<u64> =
+            adjacency.iter().map(|nbrs| nbrs.len() as u64).collect();
+        let mut distinct = count_distinct(&invariants);
+        loop {
+            let next: Vec<u64> = adjacency
+                .iter()
+                .map(|nbrs| nbrs.iter().map(|&j| invariants[j]).sum())
+                .collect();
+            let next_distinct = count_distinct(&next);
+            if next_distinct <= distinct {
+                return invariants;
+            }
+            invariants = next;
+            distinct = next_distinct;
+        }
+    }
+
+    /// A canonical visitation order over this molecule's atoms, computed
+    /// from Morgan extended-connectivity invariants: repeatedly pick the
+    /// unvisited atom with the highest invariant as the next traversal
+    /// root, visiting neighbors in descending invariant order (ties broken
+    /// by atomic number, then charge). `canonical_order()[i]` is the
+    /// original atom index that should be assigned canonical index `i+1`.
+    pub fn canonical_order(&self) -> Vec<usize> {
+        let n = self.atoms.len();
+        let adjacency = self.adjacency();
+        let invariants = self.morgan_invariants(&adjacency);
+        let rank = |i: usize| {
+            (
+                invariants[i],
+                atomic_number(self.atoms[i].symbol),
+                self.atoms[i].charge,
+            )
+        };
+
+        let mut visited = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        while order.len() < n {
+            let root = (0..n)
+                .filter(|&i| !visited[i])
+                .max_by_key(|&i| rank(i))
+                .expect("order.len() < n implies an unvisited atom exists");
+            visit(root, &adjacency, &rank, &mut visited, &mut order);
+        }
+        order
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoleculeError {
+    /// A ring-closure digit was opened but never closed.
+    UnclosedRing(usize),
+    /// A ring-closure digit appeared before any atom had been emitted.
+    LabelWithoutAtom,
+}
+
+impl Display for MoleculeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoleculeError::UnclosedRing(label) => {
+                write!(f, "ring closure `{label}` was never closed")
+            }
+            MoleculeError::LabelWithoutAtom => {
+                write!(f, "ring closure digit with no preceding atom")
+            }
+        }
+    }
+}
+
+impl Error for MoleculeError {}
+
+/// A ring-closure digit's value, e.g. `5` for both `"5"` and `"%05"`.
+fn label_value(text: &str) -> usize {
+    text.trim_start_matches('%').parse().unwrap()
+}
+
+/// Lowers `exprs` into `atoms`/`bonds`, threading `prev` (the atom to bond
+/// the next atom to) and `pending_bond` (a bond symbol seen but not yet
+/// attached) through recursive calls so that a `Branch` acts like a stack:
+/// its contents extend the graph from the current `prev`, and the branch's
+/// own changes to `prev` are discarded once it returns.
+fn lower<'a>(
+    exprs: &[Expr<'a>],
+    atoms: &mut Vec<Atom<'a>>,
+    bonds: &mut Vec<Bond<'a>>,
+    ring_labels: &mut HashMap<usize, (usize, Option<&'a str>)>,
+    prev: &mut Option<usize>,
+    pending_bond: &mut Option<&'a str>,
+) -> Result<(), MoleculeError> {
+    for e in exprs {
+        match e {
+            Expr::Atom(a) => {
+                let atom_idx = atoms.len();
+                atoms.push(a.clone());
+                if let Some(p) = *prev {
+                    bonds.push(Bond {
+                        a: p,
+                        b: atom_idx,
+                        order: pending_bond.take(),
+                    });
+                } else {
+                    pending_bond.take();
+                }
+                *prev = Some(atom_idx);
+            }
+            Expr::Bond(b) => *pending_bond = Some(b),
+            Expr::Label(l) => {
+                let cur = prev.ok_or(MoleculeError::LabelWithoutAtom)?;
+                match ring_labels.remove(&label_value(l)) {
+                    Some((open_idx, open_bond)) => bonds.push(Bond {
+                        a: open_idx,
+                        b: cur,
+                        order: open_bond.or(pending_bond.take()),
+                    }),
+                    None => {
+                        ring_labels
+                            .insert(label_value(l), (cur, pending_bond.take()));
+                    }
+                }
+            }
+            Expr::Branch(b) => {
+                let saved = *prev;
+                lower(b, atoms, bonds, ring_labels, prev, pending_bond)?;
+                *prev = saved;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Smiles<'a> {
+    /// Lowers this parse tree into a [`Molecule`] graph, resolving
+    /// ring-closure digits into bonds between the atoms that opened and
+    /// closed them. Fails if any ring digit is left open.
+    pub fn to_molecule(&self) -> Result<Molecule<'a>, MoleculeError> {
+        let mut atoms = Vec::new();
+        let mut bonds = Vec::new();
+        let mut ring_labels = HashMap::new();
+        let mut prev = None;
+        let mut pending_bond = None;
+        lower(
+            &self.exprs,
+            &mut atoms,
+            &mut bonds,
+            &mut ring_labels,
+            &mut prev,
+            &mut pending_bond,
+        )?;
+        if let Some((&label, _)) = ring_labels.iter().next() {
+            return Err(MoleculeError::UnclosedRing(label));
+        }
+        Ok(Molecule { atoms, bonds })
+    }
+}
+
+/// Parses one line of the CLI's input format: a problem id, whitespace, a
+/// SMILES string, whitespace, and a parenthesized, comma-separated list of
+/// the 0-based torsion atom indices to report.
+pub fn parse_line(s: &str) -> Result<(&str, Smiles, Vec<usize>), ParseError> {
+    let (rest, got) = tuple((
+        take_while1(AsChar::is_alphanum),
+        space1,
+        smiles,
+        space1,
+        delimited(
+            char('('),
+            separated_list1(tuple((tag(","), space0)), digit1),
+            char(')'),
+        ),
+    ))(s)
+    .map_err(|e| ParseError::from_nom(s, e))?;
+    if !rest.is_empty() {
+        let offset = s.len() - rest.len();
+        return Err(ParseError::new(s, offset, Some("line")));
+    }
+    let (pid, _space, exprs, _space2, tors) = got;
+    let tors: Vec<usize> =
+        tors.into_iter().map(|s| s.parse().unwrap()).collect();
+    Ok((pid, Smiles { exprs }, tors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let smi = std::fs::read_to_string("test.smi")
+            .unwrap()
+            .trim()
+            .to_string();
+        let got = Smiles::try_from(smi.as_str()).unwrap();
+        assert_eq!(got.to_string(), smi);
+    }
+
+    #[test]
+    fn morgan_invariants_single_atom() {
+        let smiles = Smiles::try_from("C").unwrap();
+        let molecule = smiles.to_molecule().unwrap();
+        let adjacency = molecule.adjacency();
+        assert_eq!(molecule.morgan_invariants(&adjacency), vec![0]);
+    }
+
+    #[test]
+    fn parse_line() {
+        let line = "t146j [C:1]1([H:31])=[N:2][C:3]([C:4]([C:5]([C:6](/[N:7]=[S:8](\\[N:9]([C:10]([C:11]([C:12]([N:13]([c:14]2[n:15][c:16]([H:45])[c:17]([H:46])[c:18]([H:47])[c:19]2[H:48])[C:20]([c:21]2[c:22]([H:51])[c:23]([H:52])[c:24]([Br:25])[c:26]([H:53])[c:27]2[H:54])([H:49])[H:50])([H:43])[H:44])([H:41])[H:42])([H:39])[H:40])[H:38])[C:28]([H:55])([H:56])[H:57])([H:36])[H:37])([H:34])[H:35])([H:32])[H:33])=[C:29]([H:58])[N:30]1[H:59] (9, 8, 7, 27)";
+        super::parse_line(line).unwrap();
+    }
+}